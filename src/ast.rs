@@ -1,25 +1,79 @@
-#[derive(Debug)]
-pub enum Expr {
-    Number(i64),
-    Var(String),
-    Now,
-    Binary {
-        left: Box<Expr>,
-        op: BinOp,
-        right: Box<Expr>,
-    },
-}
-
-#[derive(Debug)]
-pub enum BinOp {
-    Add,
-    Sub,
-    Mul,
-    Div,
-}
-
-#[derive(Debug)]
-pub enum Stmt {
-    Let { name: String, value: Expr },
-    Expr(Expr),
-}
+use crate::lexer::Span;
+
+#[derive(Debug, Clone)]
+pub enum Expr {
+    Number {
+        value: i64,
+        bits: u8,
+        signed: bool,
+        span: Span,
+    },
+    Var(String, Span),
+    Now(Span),
+    Binary {
+        left: Box<Expr>,
+        op: BinOp,
+        right: Box<Expr>,
+        span: Span,
+    },
+    If {
+        cond: Box<Expr>,
+        then_branch: Box<Expr>,
+        else_branch: Box<Expr>,
+        span: Span,
+    },
+    Lambda {
+        params: Vec<String>,
+        body: Box<Expr>,
+        span: Span,
+    },
+    Call {
+        callee: Box<Expr>,
+        args: Vec<Expr>,
+        span: Span,
+    },
+    Unary {
+        op: UnaryOp,
+        operand: Box<Expr>,
+        span: Span,
+    },
+}
+
+impl Expr {
+    pub fn span(&self) -> Span {
+        match self {
+            Expr::Number { span, .. } => *span,
+            Expr::Var(_, span) => *span,
+            Expr::Now(span) => *span,
+            Expr::Binary { span, .. } => *span,
+            Expr::If { span, .. } => *span,
+            Expr::Lambda { span, .. } => *span,
+            Expr::Call { span, .. } => *span,
+            Expr::Unary { span, .. } => *span,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum BinOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Pow,
+    Lt,
+    Gt,
+    Eq,
+    NotEq,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum UnaryOp {
+    Neg,
+}
+
+#[derive(Debug)]
+pub enum Stmt {
+    Let { name: String, value: Expr, span: Span },
+    Expr(Expr),
+}
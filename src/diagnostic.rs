@@ -1,21 +1,50 @@
-#[derive(Debug)]
-pub struct Diagnostic {
-    pub title: String,
-    pub message: String,
-    pub help: Option<String>,
-}
-
-impl Diagnostic {
-    pub fn new(title: &str, message: &str) -> Self {
-        Self {
-            title: title.to_string(),
-            message: message.to_string(),
-            help: None,
-        }
-    }
-
-    pub fn with_help(mut self, help: &str) -> Self {
-        self.help = Some(help.to_string());
-        self
-    }
-}
+use crate::lexer::Span;
+
+/// A secondary span on a diagnostic, annotated with text explaining its
+/// relevance (e.g. pointing at where a variable *should* have been defined).
+#[derive(Debug)]
+pub struct Label {
+    pub span: Span,
+    pub text: String,
+}
+
+#[derive(Debug)]
+pub struct Diagnostic {
+    pub title: String,
+    pub message: String,
+    pub help: Option<String>,
+    pub span: Option<Span>,
+    pub labels: Vec<Label>,
+}
+
+impl Diagnostic {
+    pub fn new(title: &str, message: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            message: message.to_string(),
+            help: None,
+            span: None,
+            labels: Vec::new(),
+        }
+    }
+
+    pub fn with_help(mut self, help: &str) -> Self {
+        self.help = Some(help.to_string());
+        self
+    }
+
+    /// Sets the primary span: the bit of source this diagnostic is about.
+    pub fn with_span(mut self, span: Span) -> Self {
+        self.span = Some(span);
+        self
+    }
+
+    /// Adds a secondary span with a short label explaining its relevance.
+    pub fn with_label(mut self, span: Span, text: &str) -> Self {
+        self.labels.push(Label {
+            span,
+            text: text.to_string(),
+        });
+        self
+    }
+}
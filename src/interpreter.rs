@@ -1,97 +1,371 @@
-use std::collections::HashMap;
-use crate::ast::*;
-
-#[derive(Debug, Clone)]
-pub enum Value {
-    Int(i64),
-    Time(i64),
-}
-
-#[derive(Debug)]
-pub struct Env {
-    vars: HashMap<String, Value>,
-}
-
-impl Env {
-    pub fn new() -> Self {
-        Self {
-            vars: HashMap::new(),
-        }
-    }
-
-    pub fn get(&self, name: &str) -> Option<Value> {
-        self.vars.get(name).cloned()
-    }
-
-    pub fn set(&mut self, name: String, value: Value) {
-        self.vars.insert(name, value);
-    }
-}
-
-pub struct Interpreter {
-    env: Env,
-    time: i64,
-}
-
-impl Interpreter {
-    pub fn new() -> Self {
-        Self {
-            env: Env::new(),
-            time: 0,
-        }
-    }
-
-    fn eval_expr(&mut self, expr: &Expr) -> Value {
-        match expr {
-            Expr::Number(n) => Value::Int(*n),
-
-            Expr::Var(name) => self.env
-                .get(name)
-                .unwrap_or_else(|| panic!("Undefined variable '{}'", name)),
-
-            Expr::Binary { left, op, right } => {
-                let l = self.eval_expr(left);
-                let r = self.eval_expr(right);
-
-                match (l, r, op) {
-                    (Value::Int(a), Value::Int(b), BinOp::Add) => Value::Int(a + b),
-                    (Value::Int(a), Value::Int(b), BinOp::Sub) => Value::Int(a - b),
-                    (Value::Int(a), Value::Int(b), BinOp::Mul) => Value::Int(a * b),
-                    (Value::Int(a), Value::Int(b), BinOp::Div) => {
-                        if b == 0 {
-                            panic!("Division by zero");
-                        }
-                        Value::Int(a / b)
-                    }
-                    _ => panic!("Type error: cannot apply binary operation to these values"),
-                }
-            }
-            Expr::Now => {
-                let t = self.time;
-                self.time += 1; // advance time deterministically
-                Value::Time(t)
-            }
-
-        }
-    }
-
-    pub fn execute(&mut self, stmts: &[Stmt]) -> Option<Value> {
-        let mut last = None;
-
-        for stmt in stmts {
-            match stmt {
-                Stmt::Let { name, value } => {
-                    let val = self.eval_expr(value);
-                    self.env.set(name.clone(), val);
-                }
-                Stmt::Expr(expr) => {
-                    last = Some(self.eval_expr(expr));
-                }
-            }
-        }
-
-        last
-    }
-}
-
-
+use std::collections::HashMap;
+use std::rc::Rc;
+use crate::ast::{BinOp, UnaryOp};
+use crate::diagnostic::Diagnostic;
+use crate::lexer::Span;
+use crate::typechecker::{TypedExpr, TypedStmt};
+use crate::types::Type;
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    Integer { value: i64, bits: u8, signed: bool },
+    Time(i64),
+    Bool(bool),
+    Closure(Rc<Closure>),
+}
+
+/// Truncates `value` to `bits` bits of two's-complement representation,
+/// sign-extending back out to `i64` when `signed` is true. This is what
+/// gives arithmetic overflow well-defined, per-width wraparound behavior.
+fn wrap(value: i64, bits: u8, signed: bool) -> i64 {
+    if bits >= 64 {
+        return value;
+    }
+    let mask = (1i64 << bits) - 1;
+    let truncated = value & mask;
+    if signed && truncated & (1i64 << (bits - 1)) != 0 {
+        truncated | !mask
+    } else {
+        truncated
+    }
+}
+
+/// A function value: its parameter names, its body, and a snapshot of the
+/// environment it was defined in (for lexical scoping).
+#[derive(Debug)]
+pub struct Closure {
+    pub params: Vec<String>,
+    pub body: TypedExpr,
+    pub env: Env,
+}
+
+#[derive(Debug, Clone)]
+pub struct Env {
+    vars: HashMap<String, Value>,
+}
+
+impl Env {
+    pub fn new() -> Self {
+        Self {
+            vars: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Value> {
+        self.vars.get(name).cloned()
+    }
+
+    pub fn set(&mut self, name: String, value: Value) {
+        self.vars.insert(name, value);
+    }
+}
+
+/// Checks that `value`'s runtime shape agrees with `ty`, the type inferred
+/// for the expression that produced it.
+fn check_runtime_type(value: &Value, ty: &Type, span: Span) -> Result<(), Diagnostic> {
+    let matches = match (value, ty) {
+        (Value::Integer { bits, signed, .. }, Type::Integer { bits: b2, signed: s2 }) => {
+            bits == b2 && signed == s2
+        }
+        (Value::Time(_), Type::Time) => true,
+        (Value::Bool(_), Type::Bool) => true,
+        (Value::Closure(_), Type::Fun(_, _)) => true,
+        _ => false,
+    };
+    if matches {
+        Ok(())
+    } else {
+        Err(Diagnostic::new(
+            "Internal Type Error",
+            "A value's runtime type didn't match the type inferred for it.",
+        )
+        .with_span(span))
+    }
+}
+
+pub struct Interpreter {
+    env: Env,
+    time: i64,
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self {
+            env: Env::new(),
+            time: 0,
+        }
+    }
+
+    /// Evaluates `expr`, then checks the result against the type
+    /// [`TypeChecker::check`] already assigned it — a defensive guard
+    /// against the typechecker and interpreter drifting out of sync.
+    fn eval_expr(&mut self, expr: &TypedExpr) -> Result<Value, Diagnostic> {
+        let value = self.eval_inner(expr)?;
+        check_runtime_type(&value, &expr.ty(), expr.span())?;
+        Ok(value)
+    }
+
+    fn eval_inner(&mut self, expr: &TypedExpr) -> Result<Value, Diagnostic> {
+        match expr {
+            TypedExpr::Number(value, ty, _) => match ty {
+                Type::Integer { bits, signed } => {
+                    Ok(Value::Integer { value: wrap(*value, *bits, *signed), bits: *bits, signed: *signed })
+                }
+                _ => unreachable!("numeric literal must have an Integer type"),
+            },
+
+            TypedExpr::Var(name, _, _) => Ok(self
+                .env
+                .get(name)
+                .unwrap_or_else(|| panic!("Undefined variable '{}'", name))),
+
+            TypedExpr::Binary { left, op, right, ty, span } => {
+                let l = self.eval_expr(left)?;
+                let r = self.eval_expr(right)?;
+
+                match (l, r) {
+                    (Value::Integer { value: a, .. }, Value::Integer { value: b, .. }) => {
+                        match op {
+                            BinOp::Lt => Ok(Value::Bool(a < b)),
+                            BinOp::Gt => Ok(Value::Bool(a > b)),
+                            BinOp::Eq => Ok(Value::Bool(a == b)),
+                            BinOp::NotEq => Ok(Value::Bool(a != b)),
+                            BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div | BinOp::Pow => {
+                                let (bits, signed) = match ty {
+                                    Type::Integer { bits, signed } => (*bits, *signed),
+                                    _ => unreachable!("arithmetic result must have an Integer type"),
+                                };
+                                let raw = match op {
+                                    BinOp::Add => a.wrapping_add(b),
+                                    BinOp::Sub => a.wrapping_sub(b),
+                                    BinOp::Mul => a.wrapping_mul(b),
+                                    BinOp::Div => {
+                                        if b == 0 {
+                                            return Err(Diagnostic::new(
+                                                "Division By Zero",
+                                                "cannot divide by zero",
+                                            )
+                                            .with_span(*span));
+                                        }
+                                        a.wrapping_div(b)
+                                    }
+                                    BinOp::Pow => {
+                                        if b < 0 {
+                                            return Err(Diagnostic::new(
+                                                "Invalid Exponent",
+                                                "exponent must be non-negative",
+                                            )
+                                            .with_span(*span));
+                                        }
+                                        let (mut result, mut base, mut exp) = (1i64, a, b as u64);
+                                        while exp > 0 {
+                                            if exp & 1 == 1 {
+                                                result = result.wrapping_mul(base);
+                                            }
+                                            base = base.wrapping_mul(base);
+                                            exp >>= 1;
+                                        }
+                                        result
+                                    }
+                                    BinOp::Lt | BinOp::Gt | BinOp::Eq | BinOp::NotEq => unreachable!(),
+                                };
+                                Ok(Value::Integer { value: wrap(raw, bits, signed), bits, signed })
+                            }
+                        }
+                    }
+                    _ => panic!("Type error: cannot apply binary operation to these values"),
+                }
+            }
+            TypedExpr::Now(..) => {
+                let t = self.time;
+                self.time += 1; // advance time deterministically
+                Ok(Value::Time(t))
+            }
+            TypedExpr::If { cond, then_branch, else_branch, .. } => match self.eval_expr(cond)? {
+                Value::Bool(true) => self.eval_expr(then_branch),
+                Value::Bool(false) => self.eval_expr(else_branch),
+                _ => panic!("Type error: if condition must be a boolean"),
+            },
+            TypedExpr::Lambda { params, body, .. } => Ok(Value::Closure(Rc::new(Closure {
+                params: params.clone(),
+                body: (**body).clone(),
+                env: self.env.clone(),
+            }))),
+            TypedExpr::Call { callee, args, span, .. } => {
+                let callee_val = self.eval_expr(callee)?;
+                let mut arg_vals = Vec::with_capacity(args.len());
+                for arg in args {
+                    arg_vals.push(self.eval_expr(arg)?);
+                }
+                self.apply(callee_val, arg_vals, callee.span(), *span)
+            }
+            TypedExpr::Unary { op, operand, ty, .. } => {
+                let v = self.eval_expr(operand)?;
+                match (op, v) {
+                    (UnaryOp::Neg, Value::Integer { value, .. }) => {
+                        let (bits, signed) = match ty {
+                            Type::Integer { bits, signed } => (*bits, *signed),
+                            _ => unreachable!("negation result must have an Integer type"),
+                        };
+                        Ok(Value::Integer { value: wrap(value.wrapping_neg(), bits, signed), bits, signed })
+                    }
+                    _ => panic!("Type error: cannot apply unary operation to this value"),
+                }
+            }
+        }
+    }
+
+    /// Applies `callee` to `arg_vals`, matching the curried function types
+    /// the typechecker already unifies calls against: fewer arguments than
+    /// the closure's parameters yields a new closure waiting for the rest
+    /// (partial application), and more arguments than it takes applies the
+    /// leftover ones to whatever the call itself returns.
+    fn apply(&mut self, callee: Value, arg_vals: Vec<Value>, callee_span: Span, span: Span) -> Result<Value, Diagnostic> {
+        let closure = match callee {
+            Value::Closure(closure) => closure,
+            _ => {
+                return Err(Diagnostic::new("Call Error", "cannot call a non-function value")
+                    .with_span(span)
+                    .with_label(callee_span, "this is not a function"))
+            }
+        };
+
+        let taken = closure.params.len().min(arg_vals.len());
+        let mut call_env = closure.env.clone();
+        let mut arg_vals = arg_vals.into_iter();
+        for param in &closure.params[..taken] {
+            call_env.set(param.clone(), arg_vals.next().unwrap());
+        }
+
+        if taken < closure.params.len() {
+            // Fewer arguments than parameters: return a closure over the
+            // remaining parameters with the given ones already bound.
+            return Ok(Value::Closure(Rc::new(Closure {
+                params: closure.params[taken..].to_vec(),
+                body: closure.body.clone(),
+                env: call_env,
+            })));
+        }
+
+        let mut call_interpreter = Interpreter { env: call_env, time: self.time };
+        let result = call_interpreter.eval_expr(&closure.body)?;
+        self.time = call_interpreter.time;
+
+        let leftover: Vec<Value> = arg_vals.collect();
+        if leftover.is_empty() {
+            Ok(result)
+        } else {
+            // More arguments than parameters: apply the rest to the result.
+            self.apply(result, leftover, callee_span, span)
+        }
+    }
+
+    pub fn execute(&mut self, stmts: &[TypedStmt]) -> Result<Option<Value>, Diagnostic> {
+        let mut last = None;
+
+        for stmt in stmts {
+            match stmt {
+                TypedStmt::Let { name, value } => {
+                    let val = self.eval_expr(value)?;
+                    self.env.set(name.clone(), val);
+                }
+                TypedStmt::Expr(expr) => {
+                    last = Some(self.eval_expr(expr)?);
+                }
+            }
+        }
+
+        Ok(last)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+    use crate::typechecker::TypeChecker;
+
+    fn run(src: &str) -> Result<Option<Value>, Diagnostic> {
+        let mut parser = Parser::new(Lexer::new(src));
+        let (stmts, errors) = parser.parse();
+        assert!(errors.is_empty(), "parse errors: {:?}", errors);
+        let typed = TypeChecker::new().check(&stmts).expect("typecheck should succeed");
+        Interpreter::new().execute(&typed)
+    }
+
+    fn int_value(result: Result<Option<Value>, Diagnostic>) -> i64 {
+        match result.unwrap().unwrap() {
+            Value::Integer { value, .. } => value,
+            other => panic!("expected an integer, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn under_application_of_multi_param_sugar_yields_a_partial_closure() {
+        // Exactly the under-application case the typechecker accepts via
+        // curried `Fun` unification: `f(1)` should return a closure over
+        // the remaining parameter, not panic on an arity mismatch.
+        let value = run("let f(x, y) = x + y\nlet a = f(1)\na(10)");
+        assert_eq!(int_value(value), 11);
+    }
+
+    #[test]
+    fn over_application_of_curried_lambdas_applies_leftover_args_to_the_result() {
+        let value = run("let f = x -> y -> x + y\nf(1, 2)");
+        assert_eq!(int_value(value), 3);
+    }
+
+    #[test]
+    fn calling_a_non_function_value_is_a_diagnostic_not_a_panic() {
+        // The typechecker already rejects this, but `apply` guards the same
+        // case defensively so an interpreter-level call error never panics.
+        let mut interpreter = Interpreter::new();
+        let err = interpreter
+            .apply(Value::Integer { value: 1, bits: 64, signed: true }, vec![], Span::new(0, 1), Span::new(0, 1))
+            .unwrap_err();
+        assert_eq!(err.title, "Call Error");
+    }
+
+    #[test]
+    fn unsigned_addition_wraps_at_its_width() {
+        assert_eq!(int_value(run("255u8 + 1u8")), 0);
+    }
+
+    #[test]
+    fn signed_addition_wraps_into_negative_at_its_width() {
+        assert_eq!(int_value(run("127i8 + 1i8")), -128);
+    }
+
+    #[test]
+    fn negation_wraps_at_its_width() {
+        // -128i8 has no positive counterpart in 8-bit two's complement, so
+        // negating it wraps back around to itself.
+        assert_eq!(int_value(run("-128i8")), -128);
+    }
+
+    #[test]
+    fn wide_values_do_not_wrap() {
+        assert_eq!(int_value(run("1000000000000i64 + 1i64")), 1000000000001);
+    }
+
+    #[test]
+    fn out_of_range_literals_wrap_at_their_width() {
+        // `300u8` has no representation in 8 bits; it should wrap the same
+        // way an arithmetic overflow does, not smuggle an impossible value
+        // through as-is.
+        assert_eq!(int_value(run("300u8")), 300 - 256);
+    }
+
+    #[test]
+    fn division_by_zero_is_a_diagnostic_not_a_panic() {
+        let err = run("1 / 0").unwrap_err();
+        assert_eq!(err.title, "Division By Zero");
+    }
+
+    #[test]
+    fn negative_exponent_is_a_diagnostic_not_a_panic() {
+        let err = run("2 ^ -1").unwrap_err();
+        assert_eq!(err.title, "Invalid Exponent");
+    }
+}
@@ -1,100 +1,208 @@
-#[derive(Debug, Clone, PartialEq)]
-pub enum Token {
-    // Keywords
-    Let,
-
-    // Identifiers & literals
-    Ident(String),
-    Number(i64),
-
-    // Operators
-    Plus,
-    Minus,
-    Star,
-    Slash,
-    Equal,
-
-    EOF,
-}
-
-pub struct Lexer {
-    input: Vec<char>,
-    pos: usize,
-}
-
-impl Lexer {
-    pub fn new(input: &str) -> Self {
-        Self {
-            input: input.chars().collect(),
-            pos: 0,
-        }
-    }
-
-    fn current(&self) -> Option<char> {
-        self.input.get(self.pos).copied()
-    }
-
-    fn advance(&mut self) {
-        self.pos += 1;
-    }
-
-    fn skip_whitespace(&mut self) {
-        while let Some(c) = self.current() {
-            if c.is_whitespace() {
-                self.advance();
-            } else {
-                break;
-            }
-        }
-    }
-
-    fn read_number(&mut self) -> i64 {
-        let mut n = String::new();
-        while let Some(c) = self.current() {
-            if c.is_ascii_digit() {
-                n.push(c);
-                self.advance();
-            } else {
-                break;
-            }
-        }
-        n.parse().unwrap()
-    }
-
-    fn read_ident(&mut self) -> String {
-        let mut s = String::new();
-        while let Some(c) = self.current() {
-            if c.is_alphanumeric() || c == '_' {
-                s.push(c);
-                self.advance();
-            } else {
-                break;
-            }
-        }
-        s
-    }
-
-    pub fn next_token(&mut self) -> Token {
-        self.skip_whitespace();
-
-        match self.current() {
-            Some('+') => { self.advance(); Token::Plus }
-            Some('-') => { self.advance(); Token::Minus }
-            Some('*') => { self.advance(); Token::Star }
-            Some('/') => { self.advance(); Token::Slash }
-            Some('=') => { self.advance(); Token::Equal }
-            Some(c) if c.is_ascii_digit() => Token::Number(self.read_number()),
-            Some(c) if c.is_alphabetic() || c == '_' => {
-                let ident = self.read_ident();
-                if ident == "let" {
-                    Token::Let
-                } else {
-                    Token::Ident(ident)
-                }
-            }
-            None => Token::EOF,
-            Some(c) => panic!("Unexpected character: {}", c),
-        }
-    }
-}
-
+use crate::diagnostic::Diagnostic;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    // Keywords
+    Let,
+    If,
+    Else,
+
+    // Identifiers & literals
+    Ident(String),
+    Number { value: i64, bits: u8, signed: bool },
+
+    // Operators
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Caret,
+    Equal,
+    Lt,
+    Gt,
+    EqEq,
+    BangEq,
+    Arrow,
+
+    // Punctuation
+    LBrace,
+    RBrace,
+    LParen,
+    RParen,
+    Comma,
+
+    EOF,
+}
+
+pub struct Lexer {
+    input: Vec<char>,
+    pos: usize,
+}
+
+impl Lexer {
+    pub fn new(input: &str) -> Self {
+        Self {
+            input: input.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn current(&self) -> Option<char> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn advance(&mut self) {
+        self.pos += 1;
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.current() {
+            if c.is_whitespace() {
+                self.advance();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Reads an integer literal, along with an optional width/signedness
+    /// suffix (`i8`, `i16`, `i32`, `i64`, `u8`, `u16`, `u32`, `u64`).
+    /// Unsuffixed literals default to `i64`.
+    fn read_number(&mut self) -> Result<(i64, u8, bool), Diagnostic> {
+        let start = self.pos;
+        let mut digits = String::new();
+        while let Some(c) = self.current() {
+            if c.is_ascii_digit() {
+                digits.push(c);
+                self.advance();
+            } else {
+                break;
+            }
+        }
+        let value: i64 = digits.parse().unwrap();
+
+        let signed = match self.current() {
+            Some('i') => true,
+            Some('u') => false,
+            _ => return Ok((value, 64, true)),
+        };
+        self.advance();
+
+        let mut width = String::new();
+        while let Some(c) = self.current() {
+            if c.is_ascii_digit() {
+                width.push(c);
+                self.advance();
+            } else {
+                break;
+            }
+        }
+
+        match width.parse::<u8>() {
+            Ok(bits @ (8 | 16 | 32 | 64)) => Ok((value, bits, signed)),
+            _ => Err(Diagnostic::new(
+                "Invalid Integer Suffix",
+                "integer suffixes must be one of i8, i16, i32, i64, u8, u16, u32, u64",
+            )
+            .with_span(Span::new(start, self.pos))),
+        }
+    }
+
+    fn read_ident(&mut self) -> String {
+        let mut s = String::new();
+        while let Some(c) = self.current() {
+            if c.is_alphanumeric() || c == '_' {
+                s.push(c);
+                self.advance();
+            } else {
+                break;
+            }
+        }
+        s
+    }
+
+    /// Lexes and returns the next token together with its char-offset span
+    /// into the original source, so downstream diagnostics can point back
+    /// at exactly the text that produced it. Returns `Err` (instead of
+    /// panicking) on an unrecognized character, so the parser can recover
+    /// and keep looking for further problems.
+    pub fn next_token(&mut self) -> Result<(Token, Span), Diagnostic> {
+        self.skip_whitespace();
+        let start = self.pos;
+
+        let token = match self.current() {
+            Some('+') => { self.advance(); Token::Plus }
+            Some('-') => {
+                self.advance();
+                if self.current() == Some('>') {
+                    self.advance();
+                    Token::Arrow
+                } else {
+                    Token::Minus
+                }
+            }
+            Some('*') => { self.advance(); Token::Star }
+            Some('/') => { self.advance(); Token::Slash }
+            Some('^') => { self.advance(); Token::Caret }
+            Some('<') => { self.advance(); Token::Lt }
+            Some('>') => { self.advance(); Token::Gt }
+            Some('{') => { self.advance(); Token::LBrace }
+            Some('}') => { self.advance(); Token::RBrace }
+            Some('(') => { self.advance(); Token::LParen }
+            Some(')') => { self.advance(); Token::RParen }
+            Some(',') => { self.advance(); Token::Comma }
+            Some('=') => {
+                self.advance();
+                if self.current() == Some('=') {
+                    self.advance();
+                    Token::EqEq
+                } else {
+                    Token::Equal
+                }
+            }
+            Some('!') => {
+                self.advance();
+                if self.current() == Some('=') {
+                    self.advance();
+                    Token::BangEq
+                } else {
+                    return Err(Diagnostic::new("Unexpected Character", "expected '=' after '!'")
+                        .with_span(Span::new(start, self.pos)));
+                }
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let (value, bits, signed) = self.read_number()?;
+                Token::Number { value, bits, signed }
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let ident = self.read_ident();
+                match ident.as_str() {
+                    "let" => Token::Let,
+                    "if" => Token::If,
+                    "else" => Token::Else,
+                    _ => Token::Ident(ident),
+                }
+            }
+            None => Token::EOF,
+            Some(c) => {
+                self.advance();
+                return Err(Diagnostic::new("Unexpected Character", &format!("unexpected character: '{}'", c))
+                    .with_span(Span::new(start, self.pos)));
+            }
+        };
+
+        Ok((token, Span::new(start, self.pos)))
+    }
+}
@@ -12,14 +12,20 @@ use interpreter::Interpreter;
 use typechecker::TypeChecker;
 use std::env;
 use std::fs;
+use std::io::{self, BufRead, Write};
 use std::path::Path;
 
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
+    if args.len() == 1 {
+        run_repl();
+        return;
+    }
+
     if args.len() != 2 {
-        eprintln!("Usage: axiom <file.axi>");
+        eprintln!("Usage: axiom [file.axi]");
         return;
     }
 
@@ -48,27 +54,150 @@ fn main() {
 }
 
 fn run_program(input: &str) {
+    let mut typechecker = TypeChecker::new();
+    let mut interpreter = Interpreter::new();
+    run_line(input, &mut typechecker, &mut interpreter);
+}
+
+/// Reads one line at a time from stdin, keeping `typechecker` and
+/// `interpreter` alive across the whole session so bindings (and the `now`
+/// counter) persist from one line to the next.
+fn run_repl() {
+    let mut typechecker = TypeChecker::new();
+    let mut interpreter = Interpreter::new();
+
+    println!("Axiom REPL — type :quit or press Ctrl-D to exit.");
+    let stdin = io::stdin();
+
+    loop {
+        print!("axiom> ");
+        if io::stdout().flush().is_err() {
+            break;
+        }
+
+        let mut line = String::new();
+        let bytes_read = match stdin.lock().read_line(&mut line) {
+            Ok(n) => n,
+            Err(err) => {
+                eprintln!("❌ Failed to read input");
+                eprintln!("→ {}", err);
+                continue;
+            }
+        };
+
+        // EOF (Ctrl-D)
+        if bytes_read == 0 {
+            println!();
+            break;
+        }
+
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line == ":quit" {
+            break;
+        }
+
+        run_line(line, &mut typechecker, &mut interpreter);
+    }
+}
+
+/// Lexes, parses, typechecks and evaluates `input` against the given
+/// (already-populated) environments, printing either the result, every
+/// parse diagnostic, or the first type or runtime diagnostic encountered.
+fn run_line(input: &str, typechecker: &mut TypeChecker, interpreter: &mut Interpreter) {
     let lexer = Lexer::new(input);
     let mut parser = Parser::new(lexer);
-    let program = parser.parse();
+    let (program, parse_diagnostics) = parser.parse();
 
-    let mut typechecker = TypeChecker::new();
-    if let Err(diag) = typechecker.check(&program) {
-        print_diagnostic(diag);
+    if !parse_diagnostics.is_empty() {
+        for diag in &parse_diagnostics {
+            print_diagnostic(input, diag);
+        }
         return;
     }
 
-    let mut interpreter = Interpreter::new();
-    let result = interpreter.execute(&program);
+    let typed_program = match typechecker.check(&program) {
+        Ok(typed_program) => typed_program,
+        Err(diag) => {
+            print_diagnostic(input, &diag);
+            return;
+        }
+    };
 
-    println!("Result: {:?}", result);
+    match interpreter.execute(&typed_program) {
+        Ok(result) => println!("Result: {:?}", result),
+        Err(diag) => print_diagnostic(input, &diag),
+    }
 }
 
-fn print_diagnostic(d: crate::diagnostic::Diagnostic) {
+fn print_diagnostic(source: &str, d: &crate::diagnostic::Diagnostic) {
     println!("\n❌ {}", d.title);
     println!("→ {}", d.message);
-    if let Some(help) = d.help {
+    if let Some(span) = d.span {
+        print_snippet(source, span, None);
+    }
+    for label in &d.labels {
+        print_snippet(source, label.span, Some(&label.text));
+    }
+    if let Some(help) = &d.help {
         println!("💡 {}", help);
     }
 }
 
+/// Renders a caret-underlined excerpt of `source` covering `span`, with an
+/// optional label printed beneath the underline.
+fn print_snippet(source: &str, span: lexer::Span, label: Option<&str>) {
+    let view = SourceView::new(source);
+    let (line_no, col, line_text) = view.locate(span.start);
+
+    // Multi-line spans clamp the underline to the end of the first line.
+    let underline_len = span.end.saturating_sub(span.start).max(1).min(line_text.len().saturating_sub(col).max(1));
+
+    let gutter = format!("  {} | ", line_no);
+    println!("{}{}", gutter, line_text);
+    println!("{}{}", " ".repeat(gutter.len() + col), "^".repeat(underline_len));
+    if let Some(text) = label {
+        println!("{}{}", " ".repeat(gutter.len() + col), text);
+    }
+}
+
+/// A view over the full source text, used to resolve char offsets into
+/// line/column positions for diagnostic rendering.
+struct SourceView<'a> {
+    chars: Vec<char>,
+    _source: &'a str,
+}
+
+impl<'a> SourceView<'a> {
+    fn new(source: &'a str) -> Self {
+        Self {
+            chars: source.chars().collect(),
+            _source: source,
+        }
+    }
+
+    /// Returns the 1-based line number, 0-based column, and text of the
+    /// line containing char offset `pos`.
+    fn locate(&self, pos: usize) -> (usize, usize, String) {
+        let pos = pos.min(self.chars.len());
+
+        let mut line_start = pos;
+        while line_start > 0 && self.chars[line_start - 1] != '\n' {
+            line_start -= 1;
+        }
+
+        let mut line_end = pos;
+        while line_end < self.chars.len() && self.chars[line_end] != '\n' {
+            line_end += 1;
+        }
+
+        let line_no = self.chars[..line_start].iter().filter(|&&c| c == '\n').count() + 1;
+        let col = pos - line_start;
+        let line_text: String = self.chars[line_start..line_end].iter().collect();
+
+        (line_no, col, line_text)
+    }
+}
+
@@ -1,127 +1,401 @@
-use crate::lexer::{Lexer, Token};
-use crate::ast::*;
-
-pub struct Parser {
-    lexer: Lexer,
-    current: Token,
-}
-
-impl Parser {
-    pub fn new(mut lexer: Lexer) -> Self {
-        let current = lexer.next_token();
-        Self { lexer, current }
-    }
-
-    fn advance(&mut self) {
-        self.current = self.lexer.next_token();
-    }
-
-    pub fn parse(&mut self) -> Vec<Stmt> {
-        let mut stmts = Vec::new();
-        while self.current != Token::EOF {
-            stmts.push(self.parse_stmt());
-        }
-        stmts
-    }
-
-    fn parse_stmt(&mut self) -> Stmt {
-        match self.current {
-            Token::Let => self.parse_let(),
-            _ => Stmt::Expr(self.parse_expr()),
-        }
-    }
-
-    fn parse_let(&mut self) -> Stmt {
-        self.advance(); // let
-
-        let name = match &self.current {
-            Token::Ident(s) => s.clone(),
-            _ => panic!("Expected identifier"),
-        };
-        self.advance();
-
-        if self.current != Token::Equal {
-            panic!("Expected '='");
-        }
-        self.advance();
-
-        let value = self.parse_expr();
-        Stmt::Let { name, value }
-    }
-
-    fn parse_expr(&mut self) -> Expr {
-        self.parse_add()
-    }
-
-    fn parse_add(&mut self) -> Expr {
-        let mut expr = self.parse_mul();
-        loop {
-            match self.current {
-                Token::Plus => {
-                    self.advance();
-                    expr = Expr::Binary {
-                        left: Box::new(expr),
-                        op: BinOp::Add,
-                        right: Box::new(self.parse_mul()),
-                    };
-                }
-                Token::Minus => {
-                    self.advance();
-                    expr = Expr::Binary {
-                        left: Box::new(expr),
-                        op: BinOp::Sub,
-                        right: Box::new(self.parse_mul()),
-                    };
-                }
-                _ => break,
-            }
-        }
-        expr
-    }
-
-    fn parse_mul(&mut self) -> Expr {
-        let mut expr = self.parse_primary();
-        loop {
-            match self.current {
-                Token::Star => {
-                    self.advance();
-                    expr = Expr::Binary {
-                        left: Box::new(expr),
-                        op: BinOp::Mul,
-                        right: Box::new(self.parse_primary()),
-                    };
-                }
-                Token::Slash => {
-                    self.advance();
-                    expr = Expr::Binary {
-                        left: Box::new(expr),
-                        op: BinOp::Div,
-                        right: Box::new(self.parse_primary()),
-                    };
-                }
-                _ => break,
-            }
-        }
-        expr
-    }
-
-    fn parse_primary(&mut self) -> Expr {
-        match &self.current {
-            Token::Number(n) => {
-                let v = *n;
-                self.advance();
-                Expr::Number(v)
-            }
-            Token::Ident(s) => {
-                let ident = s.clone();
-                self.advance();
-                if ident == "now" {
-                    Expr::Now
-                } else {
-                    Expr::Var(ident)
-                }
-            }
-            _ => panic!("Unexpected token"),
-        }
-    }
-}
+use crate::lexer::{Lexer, Span, Token};
+use crate::ast::*;
+use crate::diagnostic::Diagnostic;
+
+pub struct Parser {
+    lexer: Lexer,
+    current: Token,
+    current_span: Span,
+    errors: Vec<Diagnostic>,
+}
+
+impl Parser {
+    pub fn new(lexer: Lexer) -> Self {
+        let mut parser = Parser {
+            lexer,
+            current: Token::EOF,
+            current_span: Span::new(0, 0),
+            errors: Vec::new(),
+        };
+        parser.advance();
+        parser
+    }
+
+    /// Pulls the next token from the lexer, absorbing any lexer errors into
+    /// `self.errors` and retrying until a token is actually produced.
+    fn advance(&mut self) {
+        loop {
+            match self.lexer.next_token() {
+                Ok((token, span)) => {
+                    self.current = token;
+                    self.current_span = span;
+                    return;
+                }
+                Err(diag) => self.errors.push(diag),
+            }
+        }
+    }
+
+    /// Records a parse error at the current token, then skips tokens until
+    /// the next statement boundary (`let` or EOF) so parsing can resume.
+    fn error(&mut self, message: &str) {
+        self.errors.push(Diagnostic::new("Parse Error", message).with_span(self.current_span));
+        self.recover();
+    }
+
+    fn recover(&mut self) {
+        while self.current != Token::Let && self.current != Token::EOF {
+            self.advance();
+        }
+    }
+
+    pub fn parse(&mut self) -> (Vec<Stmt>, Vec<Diagnostic>) {
+        let mut stmts = Vec::new();
+        while self.current != Token::EOF {
+            if let Some(stmt) = self.parse_stmt() {
+                stmts.push(stmt);
+            }
+        }
+        (stmts, std::mem::take(&mut self.errors))
+    }
+
+    fn parse_stmt(&mut self) -> Option<Stmt> {
+        match self.current {
+            Token::Let => self.parse_let(),
+            _ => Some(Stmt::Expr(self.parse_expr()?)),
+        }
+    }
+
+    fn parse_let(&mut self) -> Option<Stmt> {
+        let start = self.current_span.start;
+        self.advance(); // let
+
+        let name = match &self.current {
+            Token::Ident(s) => s.clone(),
+            _ => {
+                self.error("expected identifier after 'let'");
+                return None;
+            }
+        };
+        self.advance();
+
+        // Sugar form: `let f(x, y) = body` desugars to a lambda binding.
+        if self.current == Token::LParen {
+            self.advance();
+            let mut params = Vec::new();
+            if self.current != Token::RParen {
+                loop {
+                    match &self.current {
+                        Token::Ident(p) => params.push(p.clone()),
+                        _ => {
+                            self.error("expected parameter name");
+                            return None;
+                        }
+                    }
+                    self.advance();
+                    if self.current == Token::Comma {
+                        self.advance();
+                    } else {
+                        break;
+                    }
+                }
+            }
+            self.expect(Token::RParen)?;
+            self.expect(Token::Equal)?;
+
+            let lambda_start = self.current_span.start;
+            let body = self.parse_expr()?;
+            let lambda_span = Span::new(lambda_start, body.span().end);
+            let value = Expr::Lambda {
+                params,
+                body: Box::new(body),
+                span: lambda_span,
+            };
+            let span = Span::new(start, value.span().end);
+            return Some(Stmt::Let { name, value, span });
+        }
+
+        if self.current != Token::Equal {
+            self.error("expected '=' in let binding");
+            return None;
+        }
+        self.advance();
+
+        let value = self.parse_expr()?;
+        let span = Span::new(start, value.span().end);
+        Some(Stmt::Let { name, value, span })
+    }
+
+    /// Binding power of unary minus: higher than every infix operator, so
+    /// `-a ^ b` parses as `(-a) ^ b`.
+    const PREFIX_BP: u8 = 9;
+
+    /// Left/right binding powers for each infix operator, plus the `BinOp`
+    /// it produces. Left-associative operators have `left_bp < right_bp`;
+    /// `^` is right-associative (`left_bp > right_bp`) and binds tighter
+    /// than `*`/`/`.
+    fn infix_binding_power(token: &Token) -> Option<(u8, u8, BinOp)> {
+        match token {
+            Token::Lt => Some((1, 2, BinOp::Lt)),
+            Token::Gt => Some((1, 2, BinOp::Gt)),
+            Token::EqEq => Some((1, 2, BinOp::Eq)),
+            Token::BangEq => Some((1, 2, BinOp::NotEq)),
+            Token::Plus => Some((3, 4, BinOp::Add)),
+            Token::Minus => Some((3, 4, BinOp::Sub)),
+            Token::Star => Some((5, 6, BinOp::Mul)),
+            Token::Slash => Some((5, 6, BinOp::Div)),
+            Token::Caret => Some((8, 7, BinOp::Pow)),
+            _ => None,
+        }
+    }
+
+    fn parse_expr(&mut self) -> Option<Expr> {
+        self.parse_bp(0)
+    }
+
+    /// Precedence-climbing (Pratt) parse of a single expression: parses a
+    /// prefix term, then repeatedly folds in infix operators whose left
+    /// binding power is at least `min_bp`.
+    fn parse_bp(&mut self, min_bp: u8) -> Option<Expr> {
+        let mut lhs = self.parse_prefix()?;
+
+        while let Some((left_bp, right_bp, op)) = Self::infix_binding_power(&self.current) {
+            if left_bp < min_bp {
+                break;
+            }
+            self.advance();
+            let rhs = self.parse_bp(right_bp)?;
+            let span = Span::new(lhs.span().start, rhs.span().end);
+            lhs = Expr::Binary {
+                left: Box::new(lhs),
+                op,
+                right: Box::new(rhs),
+                span,
+            };
+        }
+
+        Some(lhs)
+    }
+
+    fn parse_prefix(&mut self) -> Option<Expr> {
+        if self.current == Token::Minus {
+            let start = self.current_span.start;
+            self.advance();
+            let operand = self.parse_bp(Self::PREFIX_BP)?;
+            let span = Span::new(start, operand.span().end);
+            Some(Expr::Unary {
+                op: UnaryOp::Neg,
+                operand: Box::new(operand),
+                span,
+            })
+        } else {
+            self.parse_call()
+        }
+    }
+
+    fn parse_call(&mut self) -> Option<Expr> {
+        let mut expr = self.parse_primary()?;
+        while self.current == Token::LParen {
+            self.advance();
+            let mut args = Vec::new();
+            if self.current != Token::RParen {
+                loop {
+                    args.push(self.parse_expr()?);
+                    if self.current == Token::Comma {
+                        self.advance();
+                    } else {
+                        break;
+                    }
+                }
+            }
+            let end = self.current_span.end;
+            self.expect(Token::RParen)?;
+            let span = Span::new(expr.span().start, end);
+            expr = Expr::Call {
+                callee: Box::new(expr),
+                args,
+                span,
+            };
+        }
+        Some(expr)
+    }
+
+    fn parse_primary(&mut self) -> Option<Expr> {
+        match &self.current {
+            Token::If => self.parse_if(),
+            Token::Number { value, bits, signed } => {
+                let (value, bits, signed) = (*value, *bits, *signed);
+                let span = self.current_span;
+                self.advance();
+                Some(Expr::Number { value, bits, signed, span })
+            }
+            Token::Ident(s) => {
+                let ident = s.clone();
+                let span = self.current_span;
+                self.advance();
+                if self.current == Token::Arrow {
+                    self.advance();
+                    let body = self.parse_expr()?;
+                    let lambda_span = Span::new(span.start, body.span().end);
+                    Some(Expr::Lambda {
+                        params: vec![ident],
+                        body: Box::new(body),
+                        span: lambda_span,
+                    })
+                } else if ident == "now" {
+                    Some(Expr::Now(span))
+                } else {
+                    Some(Expr::Var(ident, span))
+                }
+            }
+            _ => {
+                self.error("unexpected token");
+                None
+            }
+        }
+    }
+
+    fn parse_if(&mut self) -> Option<Expr> {
+        let start = self.current_span.start;
+        self.advance(); // if
+
+        let cond = self.parse_expr()?;
+
+        self.expect(Token::LBrace)?;
+        let then_branch = self.parse_expr()?;
+        self.expect(Token::RBrace)?;
+
+        if self.current != Token::Else {
+            self.error("expected 'else'");
+            return None;
+        }
+        self.advance();
+
+        self.expect(Token::LBrace)?;
+        let else_branch = self.parse_expr()?;
+        let end = self.current_span.end;
+        self.expect(Token::RBrace)?;
+
+        Some(Expr::If {
+            cond: Box::new(cond),
+            then_branch: Box::new(then_branch),
+            else_branch: Box::new(else_branch),
+            span: Span::new(start, end),
+        })
+    }
+
+    fn expect(&mut self, token: Token) -> Option<()> {
+        if self.current != token {
+            self.error(&format!("expected {:?}, found {:?}", token, self.current));
+            return None;
+        }
+        self.advance();
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_expr(src: &str) -> Expr {
+        let mut parser = Parser::new(Lexer::new(src));
+        let (stmts, errors) = parser.parse();
+        assert!(errors.is_empty(), "parse errors: {:?}", errors);
+        match stmts.into_iter().next() {
+            Some(Stmt::Expr(expr)) => expr,
+            other => panic!("expected a single expression statement, got {:?}", other),
+        }
+    }
+
+    /// Renders `expr` as a fully-parenthesized prefix form, e.g. `(+ 1 (* 2 3))`,
+    /// so precedence/associativity can be asserted on the parsed shape rather
+    /// than on re-derived source text.
+    fn sexpr(expr: &Expr) -> String {
+        match expr {
+            Expr::Number { value, .. } => value.to_string(),
+            Expr::Var(name, _) => name.clone(),
+            Expr::Now(_) => "now".to_string(),
+            Expr::Binary { left, op, right, .. } => {
+                format!("({} {} {})", op_str(*op), sexpr(left), sexpr(right))
+            }
+            Expr::Unary { op, operand, .. } => format!("({} {})", unop_str(*op), sexpr(operand)),
+            Expr::If { cond, then_branch, else_branch, .. } => {
+                format!("(if {} {} {})", sexpr(cond), sexpr(then_branch), sexpr(else_branch))
+            }
+            Expr::Lambda { params, body, .. } => format!("(-> [{}] {})", params.join(" "), sexpr(body)),
+            Expr::Call { callee, args, .. } => {
+                format!("({} {})", sexpr(callee), args.iter().map(sexpr).collect::<Vec<_>>().join(" "))
+            }
+        }
+    }
+
+    fn op_str(op: BinOp) -> &'static str {
+        match op {
+            BinOp::Add => "+",
+            BinOp::Sub => "-",
+            BinOp::Mul => "*",
+            BinOp::Div => "/",
+            BinOp::Pow => "^",
+            BinOp::Lt => "<",
+            BinOp::Gt => ">",
+            BinOp::Eq => "==",
+            BinOp::NotEq => "!=",
+        }
+    }
+
+    fn unop_str(op: UnaryOp) -> &'static str {
+        match op {
+            UnaryOp::Neg => "neg",
+        }
+    }
+
+    #[test]
+    fn multiplication_binds_tighter_than_addition() {
+        assert_eq!(sexpr(&parse_expr("1 + 2 * 3")), "(+ 1 (* 2 3))");
+    }
+
+    #[test]
+    fn subtraction_is_left_associative() {
+        assert_eq!(sexpr(&parse_expr("1 - 2 - 3")), "(- (- 1 2) 3)");
+    }
+
+    #[test]
+    fn power_is_right_associative_and_binds_tighter_than_multiplication() {
+        assert_eq!(sexpr(&parse_expr("2 * 3 ^ 2 ^ 2")), "(* 2 (^ 3 (^ 2 2)))");
+    }
+
+    #[test]
+    fn unary_minus_binds_tighter_than_power() {
+        assert_eq!(sexpr(&parse_expr("-2 ^ 2")), "(^ (neg 2) 2)");
+    }
+
+    #[test]
+    fn comparisons_bind_loosest() {
+        assert_eq!(sexpr(&parse_expr("1 + 2 < 3 * 4")), "(< (+ 1 2) (* 3 4))");
+    }
+
+    #[test]
+    fn recovers_after_a_malformed_let_binding() {
+        let mut parser = Parser::new(Lexer::new("let = 1\nlet y = 2\ny"));
+        let (stmts, errors) = parser.parse();
+        assert_eq!(errors.len(), 1, "errors: {:?}", errors);
+        assert_eq!(stmts.len(), 2, "stmts: {:?}", stmts);
+    }
+
+    #[test]
+    fn recovers_through_consecutive_malformed_let_bindings() {
+        let mut parser = Parser::new(Lexer::new("let\nlet\nlet z = 5\nz"));
+        let (stmts, errors) = parser.parse();
+        assert_eq!(errors.len(), 2, "errors: {:?}", errors);
+        assert_eq!(stmts.len(), 2, "stmts: {:?}", stmts);
+    }
+
+    #[test]
+    fn a_lexer_error_does_not_abort_parsing_of_surrounding_statements() {
+        let mut parser = Parser::new(Lexer::new("let x = 1 @ 2\nlet y = 3\ny"));
+        let (stmts, errors) = parser.parse();
+        assert_eq!(errors.len(), 1, "errors: {:?}", errors);
+        assert_eq!(stmts.len(), 4, "stmts: {:?}", stmts);
+    }
+}
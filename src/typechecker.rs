@@ -1,87 +1,600 @@
-use std::collections::HashMap;
-use crate::ast::*;
-use crate::types::Type;
-use crate::diagnostic::Diagnostic;
-
-#[derive(Debug)]
-pub struct TypeEnv {
-    vars: HashMap<String, Type>,
-}
-
-impl TypeEnv {
-    pub fn new() -> Self {
-        Self {
-            vars: HashMap::new(),
-        }
-    }
-
-    pub fn get(&self, name: &str) -> Option<Type> {
-        self.vars.get(name).cloned()
-    }
-
-    pub fn set(&mut self, name: String, ty: Type) {
-        self.vars.insert(name, ty);
-    }
-}
-
-pub struct TypeChecker {
-    env: TypeEnv,
-}
-
-impl TypeChecker {
-    pub fn new() -> Self {
-        Self {
-            env: TypeEnv::new(),
-        }
-    }
-
-    fn check_expr(&mut self, expr: &Expr) -> Result<Type, Diagnostic> {
-        match expr {
-            Expr::Number(_) => Ok(Type::Int),
-            Expr::Now => Ok(Type::Time),
-            Expr::Var(name) => {
-                self.env.get(name).ok_or_else(|| {
-                    Diagnostic::new(
-                        "Unproven Variable",
-                        &format!(
-                            "The variable '{}' is used here, but no proof exists that it has been defined.",
-                            name
-                        ),
-                    )
-                    .with_help(
-                        "Define the variable before using it, or pass it as an argument.",
-                    )
-                })
-            }
-
-            Expr::Binary { left, right, .. } => {
-                let left_ty = self.check_expr(left)?;
-                let right_ty = self.check_expr(right)?;
-
-                if left_ty == Type::Int && right_ty == Type::Int {
-                    Ok(Type::Int)
-                } else {
-                    Err(Diagnostic::new(
-                        "Type Mismatch",
-                        "Both sides of this operation must have the same numeric type.",
-                    ))
-                }
-            }
-        }
-    }
-
-    pub fn check(&mut self, stmts: &[Stmt]) -> Result<(), Diagnostic> {
-        for stmt in stmts {
-            match stmt {
-                Stmt::Let { name, value } => {
-                    let ty = self.check_expr(value)?;
-                    self.env.set(name.clone(), ty);
-                }
-                Stmt::Expr(expr) => {
-                    self.check_expr(expr)?;
-                }
-            }
-        }
-        Ok(())
-    }
-}
+use std::collections::HashMap;
+use crate::ast::*;
+use crate::lexer::Span;
+use crate::types::Type;
+use crate::diagnostic::Diagnostic;
+
+/// An expression annotated with its fully-resolved type. This is the IR
+/// produced by [`TypeChecker::check`] and consumed directly by the
+/// `Interpreter`, which evaluates these nodes instead of re-inferring types
+/// off the raw `Expr` AST.
+#[derive(Debug, Clone)]
+pub enum TypedExpr {
+    Number(i64, Type, Span),
+    Var(String, Type, Span),
+    Now(Type, Span),
+    Binary {
+        left: Box<TypedExpr>,
+        op: BinOp,
+        right: Box<TypedExpr>,
+        ty: Type,
+        span: Span,
+    },
+    If {
+        cond: Box<TypedExpr>,
+        then_branch: Box<TypedExpr>,
+        else_branch: Box<TypedExpr>,
+        ty: Type,
+        span: Span,
+    },
+    Lambda {
+        params: Vec<String>,
+        body: Box<TypedExpr>,
+        ty: Type,
+        span: Span,
+    },
+    Call {
+        callee: Box<TypedExpr>,
+        args: Vec<TypedExpr>,
+        ty: Type,
+        span: Span,
+    },
+    Unary {
+        op: UnaryOp,
+        operand: Box<TypedExpr>,
+        ty: Type,
+        span: Span,
+    },
+}
+
+impl TypedExpr {
+    pub fn ty(&self) -> Type {
+        match self {
+            TypedExpr::Number(_, ty, _) => ty.clone(),
+            TypedExpr::Var(_, ty, _) => ty.clone(),
+            TypedExpr::Now(ty, _) => ty.clone(),
+            TypedExpr::Binary { ty, .. } => ty.clone(),
+            TypedExpr::If { ty, .. } => ty.clone(),
+            TypedExpr::Lambda { ty, .. } => ty.clone(),
+            TypedExpr::Call { ty, .. } => ty.clone(),
+            TypedExpr::Unary { ty, .. } => ty.clone(),
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            TypedExpr::Number(_, _, span) => *span,
+            TypedExpr::Var(_, _, span) => *span,
+            TypedExpr::Now(_, span) => *span,
+            TypedExpr::Binary { span, .. } => *span,
+            TypedExpr::If { span, .. } => *span,
+            TypedExpr::Lambda { span, .. } => *span,
+            TypedExpr::Call { span, .. } => *span,
+            TypedExpr::Unary { span, .. } => *span,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum TypedStmt {
+    Let { name: String, value: TypedExpr },
+    Expr(TypedExpr),
+}
+
+/// A (possibly) polymorphic type: `vars` are universally quantified and get
+/// freshly instantiated at each use of the binding.
+#[derive(Debug, Clone)]
+pub struct Scheme {
+    pub vars: Vec<u32>,
+    pub ty: Type,
+}
+
+#[derive(Debug, Clone, Default)]
+struct Substitution {
+    map: HashMap<u32, Type>,
+}
+
+impl Substitution {
+    fn empty() -> Self {
+        Self { map: HashMap::new() }
+    }
+
+    fn single(id: u32, ty: Type) -> Self {
+        let mut map = HashMap::new();
+        map.insert(id, ty);
+        Self { map }
+    }
+
+    fn apply(&self, ty: &Type) -> Type {
+        match ty {
+            Type::Var(id) => match self.map.get(id) {
+                Some(bound) => self.apply(bound),
+                None => Type::Var(*id),
+            },
+            Type::Fun(param, ret) => {
+                Type::Fun(Box::new(self.apply(param)), Box::new(self.apply(ret)))
+            }
+            other => other.clone(),
+        }
+    }
+
+    /// Composes `self` followed by `other`, so that `compose(other).apply(t)`
+    /// equals `other.apply(&self.apply(t))`.
+    fn compose(&self, other: &Substitution) -> Substitution {
+        let mut map: HashMap<u32, Type> = self
+            .map
+            .iter()
+            .map(|(&id, ty)| (id, other.apply(ty)))
+            .collect();
+        for (&id, ty) in &other.map {
+            map.entry(id).or_insert_with(|| ty.clone());
+        }
+        Substitution { map }
+    }
+}
+
+fn occurs(id: u32, ty: &Type) -> bool {
+    match ty {
+        Type::Var(v) => *v == id,
+        Type::Fun(param, ret) => occurs(id, param) || occurs(id, ret),
+        _ => false,
+    }
+}
+
+fn bind(id: u32, ty: Type, span: Span) -> Result<Substitution, Diagnostic> {
+    if ty == Type::Var(id) {
+        return Ok(Substitution::empty());
+    }
+    if occurs(id, &ty) {
+        return Err(Diagnostic::new(
+            "Infinite Type",
+            "This expression's type would have to contain itself.",
+        )
+        .with_span(span));
+    }
+    Ok(Substitution::single(id, ty))
+}
+
+fn unify(a: &Type, b: &Type, span: Span) -> Result<Substitution, Diagnostic> {
+    match (a, b) {
+        (Type::Var(id), other) | (other, Type::Var(id)) => bind(*id, other.clone(), span),
+        (Type::Fun(a1, a2), Type::Fun(b1, b2)) => {
+            let s1 = unify(a1, b1, span)?;
+            let s2 = unify(&s1.apply(a2), &s1.apply(b2), span)?;
+            Ok(s1.compose(&s2))
+        }
+        (Type::Integer { bits: b1, signed: s1 }, Type::Integer { bits: b2, signed: s2 }) => {
+            if b1 == b2 && s1 == s2 {
+                Ok(Substitution::empty())
+            } else {
+                Err(Diagnostic::new(
+                    "Type Mismatch",
+                    &format!("Expected type `{}`, found `{}`.", a, b),
+                )
+                .with_span(span))
+            }
+        }
+        (Type::Time, Type::Time) | (Type::Bool, Type::Bool) => Ok(Substitution::empty()),
+        _ => Err(Diagnostic::new(
+            "Type Mismatch",
+            &format!("Expected type `{}`, found `{}`.", a, b),
+        )
+        .with_span(span)),
+    }
+}
+
+/// Resolves `ty` (applying `sub`) to a concrete `Type::Integer`, defaulting
+/// an unresolved type variable to `i64` and reporting a diagnostic if it's
+/// already pinned to some other, non-integer type.
+fn expect_integer(ty: &Type, sub: &Substitution, span: Span) -> Result<(Type, Substitution), Diagnostic> {
+    let resolved = sub.apply(ty);
+    match resolved {
+        Type::Integer { .. } => Ok((resolved, Substitution::empty())),
+        Type::Var(_) => {
+            let s = unify(&resolved, &Type::I64, span)?;
+            let resolved = s.apply(&resolved);
+            Ok((resolved, s))
+        }
+        other => Err(Diagnostic::new(
+            "Type Mismatch",
+            &format!("Expected an integer type, found `{}`.", other),
+        )
+        .with_span(span)),
+    }
+}
+
+/// Re-applies `sub` to every type annotation in `expr`, recursively, so that
+/// a substitution discovered after a subexpression was already typed (e.g.
+/// while checking its siblings) doesn't leave stale, unresolved `Var`s
+/// behind in the returned IR.
+fn apply_to_typed(sub: &Substitution, expr: TypedExpr) -> TypedExpr {
+    match expr {
+        TypedExpr::Number(value, ty, span) => TypedExpr::Number(value, sub.apply(&ty), span),
+        TypedExpr::Var(name, ty, span) => TypedExpr::Var(name, sub.apply(&ty), span),
+        TypedExpr::Now(ty, span) => TypedExpr::Now(sub.apply(&ty), span),
+        TypedExpr::Binary { left, op, right, ty, span } => TypedExpr::Binary {
+            left: Box::new(apply_to_typed(sub, *left)),
+            op,
+            right: Box::new(apply_to_typed(sub, *right)),
+            ty: sub.apply(&ty),
+            span,
+        },
+        TypedExpr::If { cond, then_branch, else_branch, ty, span } => TypedExpr::If {
+            cond: Box::new(apply_to_typed(sub, *cond)),
+            then_branch: Box::new(apply_to_typed(sub, *then_branch)),
+            else_branch: Box::new(apply_to_typed(sub, *else_branch)),
+            ty: sub.apply(&ty),
+            span,
+        },
+        TypedExpr::Lambda { params, body, ty, span } => TypedExpr::Lambda {
+            params,
+            body: Box::new(apply_to_typed(sub, *body)),
+            ty: sub.apply(&ty),
+            span,
+        },
+        TypedExpr::Call { callee, args, ty, span } => TypedExpr::Call {
+            callee: Box::new(apply_to_typed(sub, *callee)),
+            args: args.into_iter().map(|arg| apply_to_typed(sub, arg)).collect(),
+            ty: sub.apply(&ty),
+            span,
+        },
+        TypedExpr::Unary { op, operand, ty, span } => TypedExpr::Unary {
+            op,
+            operand: Box::new(apply_to_typed(sub, *operand)),
+            ty: sub.apply(&ty),
+            span,
+        },
+    }
+}
+
+fn free_vars(ty: &Type, out: &mut Vec<u32>) {
+    match ty {
+        Type::Var(id) => {
+            if !out.contains(id) {
+                out.push(*id);
+            }
+        }
+        Type::Fun(param, ret) => {
+            free_vars(param, out);
+            free_vars(ret, out);
+        }
+        _ => {}
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct TypeEnv {
+    vars: HashMap<String, Scheme>,
+}
+
+impl TypeEnv {
+    pub fn new() -> Self {
+        Self {
+            vars: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Scheme> {
+        self.vars.get(name).cloned()
+    }
+
+    pub fn set(&mut self, name: String, scheme: Scheme) {
+        self.vars.insert(name, scheme);
+    }
+
+    fn remove(&mut self, name: &str) {
+        self.vars.remove(name);
+    }
+
+    /// Type variables free in the environment, i.e. not allowed to be
+    /// generalized away when quantifying a new binding's type.
+    fn free_vars(&self) -> Vec<u32> {
+        let mut out = Vec::new();
+        for scheme in self.vars.values() {
+            let mut ty_vars = Vec::new();
+            free_vars(&scheme.ty, &mut ty_vars);
+            for v in ty_vars {
+                if !scheme.vars.contains(&v) && !out.contains(&v) {
+                    out.push(v);
+                }
+            }
+        }
+        out
+    }
+}
+
+pub struct TypeChecker {
+    env: TypeEnv,
+    next_var: u32,
+}
+
+impl TypeChecker {
+    pub fn new() -> Self {
+        Self {
+            env: TypeEnv::new(),
+            next_var: 0,
+        }
+    }
+
+    fn fresh(&mut self) -> Type {
+        let id = self.next_var;
+        self.next_var += 1;
+        Type::Var(id)
+    }
+
+    fn instantiate(&mut self, scheme: &Scheme) -> Type {
+        let sub = scheme.vars.iter().fold(Substitution::empty(), |sub, &v| {
+            sub.compose(&Substitution::single(v, self.fresh()))
+        });
+        sub.apply(&scheme.ty)
+    }
+
+    fn generalize(&self, ty: &Type) -> Scheme {
+        let mut ty_vars = Vec::new();
+        free_vars(ty, &mut ty_vars);
+        let env_vars = self.env.free_vars();
+        let vars = ty_vars.into_iter().filter(|v| !env_vars.contains(v)).collect();
+        Scheme { vars, ty: ty.clone() }
+    }
+
+    /// Infers the type of `expr`, returning its typed IR node, its type
+    /// (already resolved under the returned substitution), and the
+    /// substitution accumulated while checking it.
+    fn infer(&mut self, expr: &Expr) -> Result<(TypedExpr, Type, Substitution), Diagnostic> {
+        match expr {
+            Expr::Number { value, bits, signed, span } => {
+                let ty = Type::Integer { bits: *bits, signed: *signed };
+                Ok((TypedExpr::Number(*value, ty.clone(), *span), ty, Substitution::empty()))
+            }
+            Expr::Now(span) => Ok((TypedExpr::Now(Type::Time, *span), Type::Time, Substitution::empty())),
+            Expr::Var(name, span) => {
+                let scheme = self.env.get(name).ok_or_else(|| {
+                    Diagnostic::new(
+                        "Unproven Variable",
+                        &format!(
+                            "The variable '{}' is used here, but no proof exists that it has been defined.",
+                            name
+                        ),
+                    )
+                    .with_span(*span)
+                    .with_help("Define the variable before using it, or pass it as an argument.")
+                })?;
+                let ty = self.instantiate(&scheme);
+                Ok((TypedExpr::Var(name.clone(), ty.clone(), *span), ty, Substitution::empty()))
+            }
+            Expr::Binary { left, op, right, span } => {
+                let (typed_left, left_ty, s1) = self.infer(left)?;
+                let (typed_right, right_ty, s2) = self.infer(right)?;
+                let sub = s1.compose(&s2);
+
+                let (left_ty, s3) = expect_integer(&left_ty, &sub, *span)?;
+                let sub = sub.compose(&s3);
+                let (right_ty, s4) = expect_integer(&right_ty, &sub, *span)?;
+                let sub = sub.compose(&s4);
+
+                let s5 = unify(&left_ty, &right_ty, *span)?;
+                let sub = sub.compose(&s5);
+                let operand_ty = sub.apply(&left_ty);
+
+                let result_ty = match op {
+                    BinOp::Lt | BinOp::Gt | BinOp::Eq | BinOp::NotEq => Type::Bool,
+                    BinOp::Add | BinOp::Sub | BinOp::Mul | BinOp::Div | BinOp::Pow => operand_ty,
+                };
+
+                let node = TypedExpr::Binary {
+                    left: Box::new(typed_left),
+                    op: *op,
+                    right: Box::new(typed_right),
+                    ty: result_ty.clone(),
+                    span: *span,
+                };
+                Ok((apply_to_typed(&sub, node), result_ty, sub))
+            }
+            Expr::If { cond, then_branch, else_branch, span } => {
+                let (typed_cond, cond_ty, s1) = self.infer(cond)?;
+                let s2 = unify(&s1.apply(&cond_ty), &Type::Bool, cond.span())?;
+                let sub = s1.compose(&s2);
+
+                let (typed_then, then_ty, s3) = self.infer(then_branch)?;
+                let (typed_else, else_ty, s4) = self.infer(else_branch)?;
+                let sub = sub.compose(&s3).compose(&s4);
+
+                let s5 = unify(&sub.apply(&then_ty), &sub.apply(&else_ty), *span)?;
+                let sub = sub.compose(&s5);
+                let result_ty = sub.apply(&then_ty);
+
+                let node = TypedExpr::If {
+                    cond: Box::new(typed_cond),
+                    then_branch: Box::new(typed_then),
+                    else_branch: Box::new(typed_else),
+                    ty: result_ty.clone(),
+                    span: *span,
+                };
+                Ok((apply_to_typed(&sub, node), result_ty, sub))
+            }
+            Expr::Lambda { params, body, span } => {
+                let param_tys: Vec<Type> = params.iter().map(|_| self.fresh()).collect();
+                let shadowed: Vec<Option<Scheme>> = params.iter().map(|p| self.env.get(p)).collect();
+                for (param, ty) in params.iter().zip(&param_tys) {
+                    self.env.set(param.clone(), Scheme { vars: Vec::new(), ty: ty.clone() });
+                }
+
+                let infer_result = self.infer(body);
+
+                for (param, scheme) in params.iter().zip(shadowed) {
+                    match scheme {
+                        Some(scheme) => self.env.set(param.clone(), scheme),
+                        None => self.env.remove(param),
+                    }
+                }
+                let (typed_body, body_ty, sub) = infer_result?;
+
+                let fun_ty = param_tys.iter().rev().fold(sub.apply(&body_ty), |acc, param_ty| {
+                    Type::Fun(Box::new(sub.apply(param_ty)), Box::new(acc))
+                });
+
+                let node = TypedExpr::Lambda {
+                    params: params.clone(),
+                    body: Box::new(typed_body),
+                    ty: fun_ty.clone(),
+                    span: *span,
+                };
+                Ok((apply_to_typed(&sub, node), fun_ty, sub))
+            }
+            Expr::Call { callee, args, span } => {
+                let (typed_callee, callee_ty, s0) = self.infer(callee)?;
+                let mut sub = s0;
+                let mut typed_args = Vec::new();
+                let mut arg_tys = Vec::new();
+                for arg in args {
+                    let (typed_arg, arg_ty, s) = self.infer(arg)?;
+                    sub = sub.compose(&s);
+                    typed_args.push(typed_arg);
+                    arg_tys.push(arg_ty);
+                }
+
+                let ret_ty = self.fresh();
+                let expected_fun_ty = arg_tys.iter().rev().fold(ret_ty.clone(), |acc, arg_ty| {
+                    Type::Fun(Box::new(arg_ty.clone()), Box::new(acc))
+                });
+
+                let s_unify = unify(&sub.apply(&callee_ty), &sub.apply(&expected_fun_ty), *span)?;
+                let sub = sub.compose(&s_unify);
+                let result_ty = sub.apply(&ret_ty);
+
+                let node = TypedExpr::Call {
+                    callee: Box::new(typed_callee),
+                    args: typed_args,
+                    ty: result_ty.clone(),
+                    span: *span,
+                };
+                Ok((apply_to_typed(&sub, node), result_ty, sub))
+            }
+            Expr::Unary { op, operand, span } => {
+                let (typed_operand, operand_ty, s1) = self.infer(operand)?;
+                let (resolved_ty, s2) = expect_integer(&operand_ty, &s1, *span)?;
+                let sub = s1.compose(&s2);
+
+                let node = TypedExpr::Unary {
+                    op: *op,
+                    operand: Box::new(typed_operand),
+                    ty: resolved_ty.clone(),
+                    span: *span,
+                };
+                Ok((apply_to_typed(&sub, node), resolved_ty, sub))
+            }
+        }
+    }
+
+    pub fn check(&mut self, stmts: &[Stmt]) -> Result<Vec<TypedStmt>, Diagnostic> {
+        let mut typed = Vec::new();
+        for stmt in stmts {
+            match stmt {
+                Stmt::Let { name, value, .. } => {
+                    let (typed_value, ty, sub) = self.infer(value)?;
+                    let resolved = sub.apply(&ty);
+                    let scheme = self.generalize(&resolved);
+                    self.env.set(name.clone(), scheme);
+                    typed.push(TypedStmt::Let { name: name.clone(), value: typed_value });
+                }
+                Stmt::Expr(expr) => {
+                    let (typed_expr, _, _) = self.infer(expr)?;
+                    typed.push(TypedStmt::Expr(typed_expr));
+                }
+            }
+        }
+        Ok(typed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn typed_program(src: &str) -> Vec<TypedStmt> {
+        let mut parser = Parser::new(Lexer::new(src));
+        let (stmts, errors) = parser.parse();
+        assert!(errors.is_empty(), "parse errors: {:?}", errors);
+        TypeChecker::new().check(&stmts).expect("typecheck should succeed")
+    }
+
+    /// True if no `Type::Var` remains anywhere in `ty` or in any nested
+    /// annotation of `expr`.
+    fn fully_resolved(expr: &TypedExpr) -> bool {
+        fn ty_resolved(ty: &Type) -> bool {
+            match ty {
+                Type::Var(_) => false,
+                Type::Fun(param, ret) => ty_resolved(param) && ty_resolved(ret),
+                _ => true,
+            }
+        }
+        let children_resolved = match expr {
+            TypedExpr::Number(..) | TypedExpr::Var(..) | TypedExpr::Now(..) => true,
+            TypedExpr::Binary { left, right, .. } => fully_resolved(left) && fully_resolved(right),
+            TypedExpr::If { cond, then_branch, else_branch, .. } => {
+                fully_resolved(cond) && fully_resolved(then_branch) && fully_resolved(else_branch)
+            }
+            TypedExpr::Lambda { body, .. } => fully_resolved(body),
+            TypedExpr::Call { callee, args, .. } => {
+                fully_resolved(callee) && args.iter().all(fully_resolved)
+            }
+            TypedExpr::Unary { operand, .. } => fully_resolved(operand),
+        };
+        children_resolved && ty_resolved(&expr.ty())
+    }
+
+    #[test]
+    fn final_substitution_resolves_nested_annotations() {
+        // `x` inside the lambda body is only pinned to `i64` while checking
+        // `x + 1`, after its own annotation was built; a leaked substitution
+        // would leave the lambda's param type (and the body's own `x`
+        // annotation) as an unresolved `Var`.
+        let program = typed_program("let f = x -> x + 1\nf(1)");
+        for stmt in &program {
+            let expr = match stmt {
+                TypedStmt::Let { value, .. } => value,
+                TypedStmt::Expr(expr) => expr,
+            };
+            assert!(fully_resolved(expr), "unresolved type variable leaked into {:?}", expr);
+        }
+    }
+
+    #[test]
+    fn unproven_variable_is_a_diagnostic_not_a_panic() {
+        let mut parser = Parser::new(Lexer::new("y"));
+        let (stmts, errors) = parser.parse();
+        assert!(errors.is_empty());
+        let err = TypeChecker::new().check(&stmts).unwrap_err();
+        assert_eq!(err.title, "Unproven Variable");
+    }
+
+    #[test]
+    fn mismatched_integer_widths_are_a_type_mismatch() {
+        let mut parser = Parser::new(Lexer::new("1u8 + 1u16"));
+        let (stmts, errors) = parser.parse();
+        assert!(errors.is_empty());
+        let err = TypeChecker::new().check(&stmts).unwrap_err();
+        assert_eq!(err.title, "Type Mismatch");
+    }
+
+    #[test]
+    fn mismatched_integer_signedness_is_a_type_mismatch() {
+        let mut parser = Parser::new(Lexer::new("1u64 + 1i64"));
+        let (stmts, errors) = parser.parse();
+        assert!(errors.is_empty());
+        let err = TypeChecker::new().check(&stmts).unwrap_err();
+        assert_eq!(err.title, "Type Mismatch");
+    }
+
+    #[test]
+    fn unsuffixed_literals_default_to_i64() {
+        let program = typed_program("1 + 2");
+        let ty = match &program[0] {
+            TypedStmt::Expr(expr) => expr.ty(),
+            _ => panic!("expected an expression statement"),
+        };
+        assert_eq!(ty, Type::I64);
+    }
+}
@@ -0,0 +1,29 @@
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    /// A sized integer type, e.g. `i64` (the default for unsuffixed
+    /// literals), `u32`, `i8`, ...
+    Integer { bits: u8, signed: bool },
+    Time,
+    Bool,
+    /// A type variable introduced during inference, identified by a unique id.
+    Var(u32),
+    /// A function type from parameter type to return type.
+    Fun(Box<Type>, Box<Type>),
+}
+
+impl Type {
+    /// The default integer type for an unsuffixed literal.
+    pub const I64: Type = Type::Integer { bits: 64, signed: true };
+}
+
+impl std::fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Integer { bits, signed } => write!(f, "{}{}", if *signed { "i" } else { "u" }, bits),
+            Type::Time => write!(f, "Time"),
+            Type::Bool => write!(f, "bool"),
+            Type::Var(_) => write!(f, "_"),
+            Type::Fun(param, ret) => write!(f, "{} -> {}", param, ret),
+        }
+    }
+}